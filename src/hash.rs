@@ -1,10 +1,13 @@
 use std::{
     borrow::{Borrow, BorrowMut},
-    collections::{hash_map, HashMap},
-    hash::Hash,
-    ops::{Deref, DerefMut, Index},
+    collections::{hash_map, hash_map::RandomState, HashMap, TryReserveError},
+    hash::{BuildHasher, Hash},
+    ops::{BitAnd, BitOr, BitXor, Deref, DerefMut, Index, Sub},
 };
 
+#[cfg(feature = "serde")]
+use std::{fmt, marker::PhantomData};
+
 use crate::Dual;
 
 /// A set of values that can be accessed by their key
@@ -15,21 +18,40 @@ use crate::Dual;
 /// Unlike [`std::collections::HashSet`] or [`std::collections::HashMap`], modifying a
 /// key in a way that changes its hash is *not* a logic error. The item's place in the
 /// set will be updated to reflect the new key.
+///
+/// Like the standard sets, the set is generic over the [`BuildHasher`] `S`, so a custom
+/// hasher can be supplied with [`DualHashSet::with_hasher`].
 #[derive(Clone)]
-pub struct DualHashSet<T: Dual>(HashMap<T::Key, T>);
+pub struct DualHashSet<T: Dual, S = RandomState>(HashMap<T::Key, T, S>);
 
-impl<T: Dual> Default for DualHashSet<T> {
+impl<T: Dual, S: Default> Default for DualHashSet<T, S> {
     fn default() -> Self {
         Self(HashMap::default())
     }
 }
 
-impl<T: Dual> DualHashSet<T> {
+impl<T: Dual> DualHashSet<T, RandomState> {
     /// Create a new set
     #[must_use]
     pub fn new() -> Self {
         Self::default()
     }
+    /// Create a new set with at least the given capacity
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(HashMap::with_capacity(capacity))
+    }
+}
+
+impl<T: Dual, S> DualHashSet<T, S> {
+    /// Create a new set that will use the given hasher
+    pub fn with_hasher(hasher: S) -> Self {
+        Self(HashMap::with_hasher(hasher))
+    }
+    /// Create a new set with the given capacity that will use the given hasher
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self(HashMap::with_capacity_and_hasher(capacity, hasher))
+    }
     /// Get an iterator over the keys
     pub fn keys(&self) -> Keys<T> {
         Keys(self.0.values())
@@ -40,10 +62,11 @@ impl<T: Dual> DualHashSet<T> {
     }
 }
 
-impl<T> DualHashSet<T>
+impl<T, S> DualHashSet<T, S>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
     /// Insert a value into the set
     pub fn insert(&mut self, value: T) -> Option<T> {
@@ -71,6 +94,29 @@ where
     pub fn clear(&mut self) {
         self.0.clear()
     }
+    /// Get the number of values the set can hold without reallocating
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+    /// Reserve capacity for at least `additional` more values
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+    /// Try to reserve capacity for at least `additional` more values
+    ///
+    /// Returns an error instead of panicking if the allocation fails.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.0.try_reserve(additional)
+    }
+    /// Shrink the capacity of the set as much as possible
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+    /// Shrink the capacity of the set with a lower bound
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity)
+    }
     /// Check if the set contains a value with the given key
     #[must_use]
     pub fn contains<Q>(&self, key: &Q) -> bool
@@ -97,7 +143,7 @@ where
     /// For simple modifications, prefer [`DualHashSet::modify`].
     #[allow(clippy::manual_map)]
     #[must_use]
-    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<DualHashSetRef<T>>
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<DualHashSetRef<T, S>>
     where
         Q: Hash + Eq + ?Sized,
         T::Key: Borrow<Q>,
@@ -112,7 +158,7 @@ where
         }
     }
     /// Get a value from the set, or insert a new value if it does not exist
-    pub fn get_or_insert_with<F>(&mut self, key: T::Key, f: F) -> DualHashSetRef<T>
+    pub fn get_or_insert_with<F>(&mut self, key: T::Key, f: F) -> DualHashSetRef<T, S>
     where
         F: FnOnce(T::Key) -> T,
     {
@@ -121,6 +167,19 @@ where
         }
         DualHashSetRef { key, set: self }
     }
+    /// Get the [`Entry`] for a key, allowing in-place manipulation based on whether a
+    /// value with that key is already present.
+    ///
+    /// Like [`DualHashSet::get_mut`], the [`DualHashSetRef`] returned by the entry relocates
+    /// its value if the key changes. The entry records only the key, not the map slot, so
+    /// the returned reference looks the key up again on each access.
+    pub fn entry(&mut self, key: T::Key) -> Entry<T, S> {
+        if self.contains(&key) {
+            Entry::Occupied(OccupiedEntry { set: self, key })
+        } else {
+            Entry::Vacant(VacantEntry { set: self, key })
+        }
+    }
     /// Modify a value in the set.
     /// If the key changes, the value will be moved to the new key.
     pub fn modify<Q, F, R>(&mut self, key: &Q, mut f: F) -> Option<R>
@@ -153,6 +212,27 @@ where
             true
         });
     }
+    /// Remove all values from the set, returning them in an iterator
+    ///
+    /// The set is emptied even if the iterator is not fully consumed.
+    pub fn drain(&mut self) -> Drain<T> {
+        Drain(self.0.drain())
+    }
+    /// Remove and yield every value for which the predicate returns `true`, retaining the rest
+    ///
+    /// Because the predicate receives `&mut T`, a retained value whose key is changed is
+    /// relocated to its new key, exactly as [`DualHashSet::retain`] does.
+    pub fn extract_if<F>(&mut self, predicate: F) -> ExtractIf<T, S, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let keys = self.keys().cloned().collect::<Vec<_>>().into_iter();
+        ExtractIf {
+            set: self,
+            keys,
+            predicate,
+        }
+    }
     /// Remove all values from the set that do not satisfy the predicate
     pub fn retain<F>(&mut self, mut predicate: F)
     where
@@ -171,11 +251,123 @@ where
     }
 }
 
-impl<Q, T> Index<&Q> for DualHashSet<T>
+impl<T, S> DualHashSet<T, S>
+where
+    T: Dual + Clone,
+    T::Key: Hash,
+    S: BuildHasher + Default,
+{
+    /// Return a new set containing every value whose key is present in either set
+    ///
+    /// Because values are compared by their [`Dual::key`], when the same key is present
+    /// in both sets the value from `self` is kept.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = Self::default();
+        for value in other {
+            set.insert(value.clone());
+        }
+        for value in self {
+            set.insert(value.clone());
+        }
+        set
+    }
+    /// Return a new set containing every value whose key is present in both sets
+    ///
+    /// On a key collision the value from `self` is kept.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut set = Self::default();
+        for value in self {
+            if other.contains(value.key()) {
+                set.insert(value.clone());
+            }
+        }
+        set
+    }
+    /// Return a new set containing every value whose key is present in `self` but not `other`
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut set = Self::default();
+        for value in self {
+            if !other.contains(value.key()) {
+                set.insert(value.clone());
+            }
+        }
+        set
+    }
+    /// Return a new set containing every value whose key is present in exactly one of the sets
+    #[must_use]
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        let mut set = Self::default();
+        for value in self {
+            if !other.contains(value.key()) {
+                set.insert(value.clone());
+            }
+        }
+        for value in other {
+            if !self.contains(value.key()) {
+                set.insert(value.clone());
+            }
+        }
+        set
+    }
+}
+
+impl<T, S> BitOr for &DualHashSet<T, S>
+where
+    T: Dual + Clone,
+    T::Key: Hash,
+    S: BuildHasher + Default,
+{
+    type Output = DualHashSet<T, S>;
+    fn bitor(self, other: Self) -> Self::Output {
+        self.union(other)
+    }
+}
+
+impl<T, S> BitAnd for &DualHashSet<T, S>
+where
+    T: Dual + Clone,
+    T::Key: Hash,
+    S: BuildHasher + Default,
+{
+    type Output = DualHashSet<T, S>;
+    fn bitand(self, other: Self) -> Self::Output {
+        self.intersection(other)
+    }
+}
+
+impl<T, S> BitXor for &DualHashSet<T, S>
+where
+    T: Dual + Clone,
+    T::Key: Hash,
+    S: BuildHasher + Default,
+{
+    type Output = DualHashSet<T, S>;
+    fn bitxor(self, other: Self) -> Self::Output {
+        self.symmetric_difference(other)
+    }
+}
+
+impl<T, S> Sub for &DualHashSet<T, S>
+where
+    T: Dual + Clone,
+    T::Key: Hash,
+    S: BuildHasher + Default,
+{
+    type Output = DualHashSet<T, S>;
+    fn sub(self, other: Self) -> Self::Output {
+        self.difference(other)
+    }
+}
+
+impl<Q, T, S> Index<&Q> for DualHashSet<T, S>
 where
     Q: Hash + Eq + ?Sized,
     T: Dual,
     T::Key: Hash + Eq + Borrow<Q>,
+    S: BuildHasher,
 {
     type Output = T;
     #[track_caller]
@@ -193,6 +385,16 @@ pub struct Iter<'a, T: Dual>(hash_map::Values<'a, T::Key, T>);
 /// Iterator returned by [`DualHashSet::into_iter`]
 #[must_use]
 pub struct IntoIter<T: Dual>(hash_map::IntoValues<T::Key, T>);
+/// Iterator returned by [`DualHashSet::drain`]
+#[must_use]
+pub struct Drain<'a, T: Dual>(hash_map::Drain<'a, T::Key, T>);
+
+impl<'a, T: Dual> Iterator for Drain<'a, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+}
 
 impl<'a, T: Dual> Iterator for Keys<'a, T> {
     type Item = &'a T::Key;
@@ -215,7 +417,7 @@ impl<T: Dual> Iterator for IntoIter<T> {
     }
 }
 
-impl<T: Dual> IntoIterator for DualHashSet<T> {
+impl<T: Dual, S> IntoIterator for DualHashSet<T, S> {
     type Item = T;
     type IntoIter = IntoIter<T>;
     fn into_iter(self) -> Self::IntoIter {
@@ -223,7 +425,7 @@ impl<T: Dual> IntoIterator for DualHashSet<T> {
     }
 }
 
-impl<'a, T: Dual> IntoIterator for &'a DualHashSet<T> {
+impl<'a, T: Dual, S> IntoIterator for &'a DualHashSet<T, S> {
     type Item = &'a T;
     type IntoIter = Iter<'a, T>;
     fn into_iter(self) -> Self::IntoIter {
@@ -236,19 +438,21 @@ impl<'a, T: Dual> IntoIterator for &'a DualHashSet<T> {
 /// When the reference is dropped, the value will be moved to the new
 /// key if it has changed.
 #[must_use]
-pub struct DualHashSetRef<'a, T>
+pub struct DualHashSetRef<'a, T, S = RandomState>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
-    set: &'a mut DualHashSet<T>,
+    set: &'a mut DualHashSet<T, S>,
     key: T::Key,
 }
 
-impl<'a, T> Deref for DualHashSetRef<'a, T>
+impl<'a, T, S> Deref for DualHashSetRef<'a, T, S>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
     type Target = T;
     fn deref(&self) -> &Self::Target {
@@ -256,60 +460,66 @@ where
     }
 }
 
-impl<'a, T> DerefMut for DualHashSetRef<'a, T>
+impl<'a, T, S> DerefMut for DualHashSetRef<'a, T, S>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.set.0.get_mut(&self.key).unwrap()
     }
 }
 
-impl<'a, T> AsRef<T> for DualHashSetRef<'a, T>
+impl<'a, T, S> AsRef<T> for DualHashSetRef<'a, T, S>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
     fn as_ref(&self) -> &T {
         self
     }
 }
 
-impl<'a, T> AsMut<T> for DualHashSetRef<'a, T>
+impl<'a, T, S> AsMut<T> for DualHashSetRef<'a, T, S>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
     fn as_mut(&mut self) -> &mut T {
         self
     }
 }
 
-impl<'a, T> Borrow<T> for DualHashSetRef<'a, T>
+impl<'a, T, S> Borrow<T> for DualHashSetRef<'a, T, S>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
     fn borrow(&self) -> &T {
         self
     }
 }
 
-impl<'a, T> BorrowMut<T> for DualHashSetRef<'a, T>
+impl<'a, T, S> BorrowMut<T> for DualHashSetRef<'a, T, S>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
     fn borrow_mut(&mut self) -> &mut T {
         self
     }
 }
 
-impl<'a, T> Drop for DualHashSetRef<'a, T>
+impl<'a, T, S> Drop for DualHashSetRef<'a, T, S>
 where
     T: Dual,
     T::Key: Hash,
+    S: BuildHasher,
 {
     fn drop(&mut self) {
         let new_key = self.key();
@@ -321,11 +531,284 @@ where
     }
 }
 
+/// Iterator returned by [`DualHashSet::extract_if`]
+#[must_use]
+pub struct ExtractIf<'a, T, S, F>
+where
+    T: Dual,
+    T::Key: Hash,
+    S: BuildHasher,
+    F: FnMut(&mut T) -> bool,
+{
+    set: &'a mut DualHashSet<T, S>,
+    keys: std::vec::IntoIter<T::Key>,
+    predicate: F,
+}
+
+impl<'a, T, S, F> Iterator for ExtractIf<'a, T, S, F>
+where
+    T: Dual,
+    T::Key: Hash,
+    S: BuildHasher,
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        for key in self.keys.by_ref() {
+            let value = self.set.0.get_mut(&key).unwrap();
+            let extract = (self.predicate)(value);
+            let new_key = value.key().clone();
+            if extract {
+                return Some(self.set.0.remove(&key).unwrap());
+            } else if new_key != key {
+                let value = self.set.0.remove(&key).unwrap();
+                self.set.0.insert(new_key, value);
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T, S, F> Drop for ExtractIf<'a, T, S, F>
+where
+    T: Dual,
+    T::Key: Hash,
+    S: BuildHasher,
+    F: FnMut(&mut T) -> bool,
+{
+    fn drop(&mut self) {
+        while self.next().is_some() {}
+    }
+}
+
+/// A view into a single key in a [`DualHashSet`], which may be occupied or vacant
+///
+/// Returned by [`DualHashSet::entry`].
+#[must_use]
+pub enum Entry<'a, T, S = RandomState>
+where
+    T: Dual,
+    T::Key: Hash,
+{
+    /// A key that is already present in the set
+    Occupied(OccupiedEntry<'a, T, S>),
+    /// A key that is not present in the set
+    Vacant(VacantEntry<'a, T, S>),
+}
+
+/// A view into an occupied key in a [`DualHashSet`]
+///
+/// Part of [`Entry`].
+#[must_use]
+pub struct OccupiedEntry<'a, T, S = RandomState>
+where
+    T: Dual,
+    T::Key: Hash,
+{
+    set: &'a mut DualHashSet<T, S>,
+    key: T::Key,
+}
+
+/// A view into a vacant key in a [`DualHashSet`]
+///
+/// Part of [`Entry`].
+#[must_use]
+pub struct VacantEntry<'a, T, S = RandomState>
+where
+    T: Dual,
+    T::Key: Hash,
+{
+    set: &'a mut DualHashSet<T, S>,
+    key: T::Key,
+}
+
+impl<'a, T, S> Entry<'a, T, S>
+where
+    T: Dual,
+    T::Key: Hash,
+    S: BuildHasher,
+{
+    /// Get the key of this entry
+    #[must_use]
+    pub fn key(&self) -> &T::Key {
+        match self {
+            Entry::Occupied(entry) => &entry.key,
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+    /// Ensure a value is present by inserting the result of `f` if the key is vacant,
+    /// then return a mutable reference to the value
+    ///
+    /// The closure is given the entry's key.
+    pub fn or_insert_with<F>(self, f: F) -> DualHashSetRef<'a, T, S>
+    where
+        F: FnOnce(T::Key) -> T,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.or_insert_with(f),
+        }
+    }
+    /// Ensure a value is present by inserting `default` if the key is vacant,
+    /// then return a mutable reference to the value
+    pub fn or_insert(self, default: T) -> DualHashSetRef<'a, T, S> {
+        self.or_insert_with(|_| default)
+    }
+    /// Run `f` on the value if the key is occupied, relocating it if the key changes
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut T),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            let value = entry.set.0.get_mut(&entry.key).unwrap();
+            f(value);
+            let new_key = value.key().clone();
+            if new_key != entry.key {
+                let value = entry.set.0.remove(&entry.key).unwrap();
+                entry.set.0.insert(new_key.clone(), value);
+                entry.key = new_key;
+            }
+        }
+        self
+    }
+}
+
+impl<'a, T, S> OccupiedEntry<'a, T, S>
+where
+    T: Dual,
+    T::Key: Hash,
+    S: BuildHasher,
+{
+    /// Get the key of this entry
+    #[must_use]
+    pub fn key(&self) -> &T::Key {
+        &self.key
+    }
+    /// Get a reference to the value
+    #[must_use]
+    pub fn get(&self) -> &T {
+        self.set.0.get(&self.key).unwrap()
+    }
+    /// Get a mutable reference to the value
+    ///
+    /// When the reference is dropped, the value will be moved to the new key if it has
+    /// changed.
+    pub fn get_mut(self) -> DualHashSetRef<'a, T, S> {
+        self.into_mut()
+    }
+    /// Convert the entry into a mutable reference to the value
+    ///
+    /// When the reference is dropped, the value will be moved to the new key if it has
+    /// changed.
+    pub fn into_mut(self) -> DualHashSetRef<'a, T, S> {
+        DualHashSetRef {
+            key: self.key,
+            set: self.set,
+        }
+    }
+}
+
+impl<'a, T, S> VacantEntry<'a, T, S>
+where
+    T: Dual,
+    T::Key: Hash,
+    S: BuildHasher,
+{
+    /// Get the key of this entry
+    #[must_use]
+    pub fn key(&self) -> &T::Key {
+        &self.key
+    }
+    /// Take ownership of the key
+    #[must_use]
+    pub fn into_key(self) -> T::Key {
+        self.key
+    }
+    /// Insert the result of `f` and return a mutable reference to it
+    ///
+    /// The closure is given the entry's key.
+    pub fn or_insert_with<F>(self, f: F) -> DualHashSetRef<'a, T, S>
+    where
+        F: FnOnce(T::Key) -> T,
+    {
+        let value = f(self.key.clone());
+        let key = value.key().clone();
+        self.set.insert(value);
+        DualHashSetRef {
+            key,
+            set: self.set,
+        }
+    }
+    /// Insert `value` and return a mutable reference to it
+    pub fn or_insert(self, value: T) -> DualHashSetRef<'a, T, S> {
+        let key = value.key().clone();
+        self.set.insert(value);
+        DualHashSetRef {
+            key,
+            set: self.set,
+        }
+    }
+}
+
+/// The values are serialized as a flat sequence, since each value's key is derived from it.
+#[cfg(feature = "serde")]
+impl<T, S> serde::Serialize for DualHashSet<T, S>
+where
+    T: Dual + serde::Serialize,
+{
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Each value in the sequence is filed under its own [`Dual::key`].
+#[cfg(feature = "serde")]
+impl<'de, T, S> serde::Deserialize<'de> for DualHashSet<T, S>
+where
+    T: Dual + serde::Deserialize<'de>,
+    T::Key: Hash,
+    S: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct SeqVisitor<T, S>(PhantomData<(T, S)>);
+
+        impl<'de, T, S> serde::de::Visitor<'de> for SeqVisitor<T, S>
+        where
+            T: Dual + serde::Deserialize<'de>,
+            T::Key: Hash,
+            S: BuildHasher + Default,
+        {
+            type Value = DualHashSet<T, S>;
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a sequence of values")
+            }
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut set = DualHashSet::default();
+                while let Some(value) = seq.next_element()? {
+                    set.insert(value);
+                }
+                Ok(set)
+            }
+        }
+
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
-    #[derive(PartialEq, Eq)]
+    #[derive(Clone, PartialEq, Eq)]
     struct Test {
         key: String,
         value: u32,
@@ -377,6 +860,133 @@ mod test {
         assert_eq!(set["three"].key, "three");
     }
     #[test]
+    fn set_algebra() {
+        fn set<I: IntoIterator<Item = u32>>(iter: I) -> DualHashSet<Test> {
+            let mut set = DualHashSet::new();
+            for i in iter {
+                set.insert(Test {
+                    key: i.to_string(),
+                    value: i,
+                });
+            }
+            set
+        }
+        let a = set(0..5);
+        let b = set(3..8);
+        let union = &a | &b;
+        assert_eq!(union.len(), 8);
+        for i in 0..8 {
+            assert!(union.contains(&i.to_string()));
+        }
+        let intersection = &a & &b;
+        assert_eq!(intersection.len(), 2);
+        assert!(intersection.contains("3"));
+        assert!(intersection.contains("4"));
+        let difference = &a - &b;
+        assert_eq!(difference.len(), 3);
+        for i in 0..3 {
+            assert!(difference.contains(&i.to_string()));
+        }
+        let symmetric = &a ^ &b;
+        assert_eq!(symmetric.len(), 6);
+        assert!(!symmetric.contains("3"));
+        assert!(!symmetric.contains("4"));
+    }
+    #[test]
+    fn drain() {
+        let mut set = DualHashSet::new();
+        for i in 0..10 {
+            set.insert(Test {
+                key: i.to_string(),
+                value: i,
+            });
+        }
+        let drained: Vec<_> = set.drain().collect();
+        assert_eq!(drained.len(), 10);
+        assert!(set.is_empty());
+    }
+    #[test]
+    fn extract_if() {
+        let mut set = DualHashSet::new();
+        for i in 0..10 {
+            set.insert(Test {
+                key: i.to_string(),
+                value: i,
+            });
+        }
+        // Extract the even values, and re-key the odd ones that are retained.
+        let extracted: Vec<_> = set
+            .extract_if(|test| {
+                test.key = format!("k{}", test.value);
+                test.value % 2 == 0
+            })
+            .collect();
+        assert_eq!(extracted.len(), 5);
+        assert_eq!(set.len(), 5);
+        for i in 0..10 {
+            if i % 2 == 0 {
+                assert!(!set.contains(&format!("k{i}")));
+            } else {
+                assert_eq!(set[&format!("k{i}")].value, i);
+            }
+            assert!(!set.contains(&i.to_string()));
+        }
+    }
+    #[test]
+    fn entry() {
+        let mut set = DualHashSet::new();
+        for i in 0..10 {
+            set.insert(Test {
+                key: i.to_string(),
+                value: i,
+            });
+        }
+        // Vacant inserts
+        let _ = set.entry("x".into()).or_insert_with(|key| Test { key, value: 42 });
+        assert_eq!(set["x"].value, 42);
+        // Occupied relocates when the key is changed through the returned reference
+        {
+            let mut value = set.entry("3".into()).or_insert_with(|key| Test { key, value: 0 });
+            (*value).key = "three".into();
+        }
+        assert!(!set.contains("3"));
+        assert_eq!(set["three"].value, 3);
+        // and_modify only runs on the occupied branch
+        let _ = set.entry("4".into()).and_modify(|test| test.value += 100);
+        let _ = set.entry("y".into()).and_modify(|test| test.value += 100);
+        assert_eq!(set["4"].value, 104);
+        assert!(!set.contains("y"));
+    }
+    #[test]
+    fn with_hasher() {
+        let mut set = DualHashSet::with_hasher(RandomState::new());
+        for i in 0..10 {
+            set.insert(Test {
+                key: i.to_string(),
+                value: i,
+            });
+        }
+        assert_eq!(set.len(), 10);
+        assert_eq!(set["5"].value, 5);
+    }
+    #[test]
+    fn capacity() {
+        let mut set: DualHashSet<Test> = DualHashSet::with_capacity(100);
+        assert!(set.capacity() >= 100);
+        set.reserve(200);
+        assert!(set.capacity() >= 200);
+        set.try_reserve(10).unwrap();
+        for i in 0..10 {
+            set.insert(Test {
+                key: i.to_string(),
+                value: i,
+            });
+        }
+        set.shrink_to_fit();
+        assert!(set.capacity() >= 10);
+        assert_eq!(set.len(), 10);
+    }
+    #[test]
     fn retain() {
         let mut set = DualHashSet::new();
         for i in 0..10 {
@@ -7,6 +7,12 @@
 pub mod hash;
 pub use hash::DualHashSet;
 
+/// Immutable [`DualSet`] with structural sharing
+pub mod persistent;
+pub use persistent::DualSet;
+
+pub use std::collections::TryReserveError;
+
 /// A value that contains its own key
 pub trait Dual {
     /// The key type
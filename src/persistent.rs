@@ -0,0 +1,430 @@
+use std::{
+    borrow::Borrow,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use crate::Dual;
+
+/// The number of bits of the hash consumed at each level of the trie
+const BITS: u32 = 5;
+/// The branching factor of the trie
+const BRANCHING: usize = 1 << BITS;
+/// The mask used to extract a single level's index from a hash
+const MASK: u64 = BRANCHING as u64 - 1;
+
+/// An immutable set of values that can be accessed by their key
+///
+/// Like [`DualHashSet`](crate::DualHashSet), values must implement the [`Dual`] trait,
+/// but the set is backed by a hash-array-mapped trie of reference-counted nodes rather than
+/// a [`HashMap`](std::collections::HashMap). [`insert`](DualSet::insert),
+/// [`remove`](DualSet::remove), and [`modify`](DualSet::modify) return a new set in
+/// `O(log n)`, sharing all untouched nodes with the original, so cloning a large set before
+/// a speculative edit is cheap and the set can be shared freely across threads.
+///
+/// As with [`DualHashSet`](crate::DualHashSet), changing a value's key relocates it; here
+/// that is expressed by returning a new set rather than mutating in place.
+pub struct DualSet<T: Dual> {
+    root: Option<Arc<Node<T>>>,
+    len: usize,
+}
+
+enum Node<T: Dual> {
+    /// A bucket of entries whose keys share a hash
+    Leaf { hash: u64, entries: Vec<(T::Key, T)> },
+    /// An interior node indexed by a 5-bit slice of the hash
+    Branch(Box<[Option<Arc<Node<T>>>; BRANCHING]>),
+}
+
+impl<T: Dual> Clone for DualSet<T> {
+    fn clone(&self) -> Self {
+        DualSet {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Dual> Default for DualSet<T> {
+    fn default() -> Self {
+        DualSet {
+            root: None,
+            len: 0,
+        }
+    }
+}
+
+/// Hash a key with the trie's fixed hasher
+fn hash_key<Q>(key: &Q) -> u64
+where
+    Q: Hash + ?Sized,
+{
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<T: Dual> DualSet<T> {
+    /// Create a new empty set
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Get the number of values in the set
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Check if the set is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Get an iterator over the values
+    pub fn iter(&self) -> Iter<T> {
+        let empty: &[(T::Key, T)] = &[];
+        Iter {
+            stack: self.root.iter().map(AsRef::as_ref).collect(),
+            leaf: empty.iter(),
+        }
+    }
+    /// Check if the set contains a value with the given key
+    #[must_use]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T::Key: Hash + Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+    /// Get a value from the set
+    #[must_use]
+    pub fn get<Q>(&self, key: &Q) -> Option<&T>
+    where
+        Q: Hash + Eq + ?Sized,
+        T::Key: Hash + Borrow<Q>,
+    {
+        let hash = hash_key(key);
+        let mut node = self.root.as_deref()?;
+        let mut shift = 0;
+        loop {
+            match node {
+                Node::Leaf { entries, .. } => {
+                    return entries
+                        .iter()
+                        .find(|(k, _)| k.borrow() == key)
+                        .map(|(_, value)| value);
+                }
+                Node::Branch(children) => {
+                    let idx = ((hash >> shift) & MASK) as usize;
+                    node = children[idx].as_deref()?;
+                    shift += BITS;
+                }
+            }
+        }
+    }
+}
+
+impl<T: Dual> DualSet<T>
+where
+    T: Clone,
+    T::Key: Hash,
+{
+    /// Insert a value, returning a new set
+    ///
+    /// The value is filed under its own [`Dual::key`]. Only the nodes along the value's
+    /// hash path are cloned; the rest of the set is shared with the original.
+    #[must_use]
+    pub fn insert(&self, value: T) -> Self {
+        let hash = hash_key(value.key());
+        let key = value.key().clone();
+        let (root, added) = match &self.root {
+            Some(node) => Node::insert(node, 0, hash, key, value),
+            None => (
+                Arc::new(Node::Leaf {
+                    hash,
+                    entries: vec![(key, value)],
+                }),
+                true,
+            ),
+        };
+        DualSet {
+            root: Some(root),
+            len: self.len + usize::from(added),
+        }
+    }
+    /// Remove the value with the given key, returning a new set
+    ///
+    /// If the key is not present, a clone of the original set is returned.
+    #[must_use]
+    pub fn remove<Q>(&self, key: &Q) -> Self
+    where
+        Q: Hash + Eq + ?Sized,
+        T::Key: Borrow<Q>,
+    {
+        let hash = hash_key(key);
+        match &self.root {
+            Some(root) => match Node::remove(root, 0, hash, key) {
+                Some(root) => DualSet {
+                    root,
+                    len: self.len - 1,
+                },
+                None => self.clone(),
+            },
+            None => self.clone(),
+        }
+    }
+    /// Apply `f` to a clone of the value with the given key, returning a new set
+    ///
+    /// If `f` changes the key, the value is removed from its old hash path and inserted
+    /// along the new one.
+    #[must_use]
+    pub fn modify<Q, F>(&self, key: &Q, f: F) -> Self
+    where
+        Q: Hash + Eq + ?Sized,
+        T::Key: Borrow<Q>,
+        F: FnOnce(&mut T),
+    {
+        if let Some(value) = self.get(key) {
+            let mut value = value.clone();
+            f(&mut value);
+            if value.key().borrow() == key {
+                self.insert(value)
+            } else {
+                self.remove(key).insert(value)
+            }
+        } else {
+            self.clone()
+        }
+    }
+}
+
+impl<T> Node<T>
+where
+    T: Dual + Clone,
+    T::Key: Hash,
+{
+    fn insert(
+        node: &Arc<Node<T>>,
+        shift: u32,
+        hash: u64,
+        key: T::Key,
+        value: T,
+    ) -> (Arc<Node<T>>, bool) {
+        match &**node {
+            Node::Leaf {
+                hash: leaf_hash,
+                entries,
+            } => {
+                if *leaf_hash == hash {
+                    let mut entries = entries.clone();
+                    if let Some(slot) = entries.iter_mut().find(|(k, _)| *k == key) {
+                        slot.1 = value;
+                        (Arc::new(Node::Leaf { hash, entries }), false)
+                    } else {
+                        entries.push((key, value));
+                        (Arc::new(Node::Leaf { hash, entries }), true)
+                    }
+                } else {
+                    // The hashes differ, so split this leaf into a branch and retry.
+                    let mut children: Box<[Option<Arc<Node<T>>>; BRANCHING]> = Box::default();
+                    let idx = ((leaf_hash >> shift) & MASK) as usize;
+                    children[idx] = Some(node.clone());
+                    let branch = Arc::new(Node::Branch(children));
+                    Node::insert(&branch, shift, hash, key, value)
+                }
+            }
+            Node::Branch(children) => {
+                let idx = ((hash >> shift) & MASK) as usize;
+                let mut children = children.clone();
+                let (child, added) = match &children[idx] {
+                    Some(child) => Node::insert(child, shift + BITS, hash, key, value),
+                    None => (
+                        Arc::new(Node::Leaf {
+                            hash,
+                            entries: vec![(key, value)],
+                        }),
+                        true,
+                    ),
+                };
+                children[idx] = Some(child);
+                (Arc::new(Node::Branch(children)), added)
+            }
+        }
+    }
+    /// Remove a key, returning `Some(new_subtree)` if it was present (`None` inside the
+    /// option means the subtree is now empty) or `None` if it was absent.
+    fn remove<Q>(
+        node: &Arc<Node<T>>,
+        shift: u32,
+        hash: u64,
+        key: &Q,
+    ) -> Option<Option<Arc<Node<T>>>>
+    where
+        Q: Hash + Eq + ?Sized,
+        T::Key: Borrow<Q>,
+    {
+        match &**node {
+            Node::Leaf {
+                hash: leaf_hash,
+                entries,
+            } => {
+                let pos = entries.iter().position(|(k, _)| k.borrow() == key)?;
+                if entries.len() == 1 {
+                    Some(None)
+                } else {
+                    let mut entries = entries.clone();
+                    entries.remove(pos);
+                    Some(Some(Arc::new(Node::Leaf {
+                        hash: *leaf_hash,
+                        entries,
+                    })))
+                }
+            }
+            Node::Branch(children) => {
+                let idx = ((hash >> shift) & MASK) as usize;
+                let child = children[idx].as_ref()?;
+                let new_child = Node::remove(child, shift + BITS, hash, key)?;
+                let mut children = children.clone();
+                children[idx] = new_child;
+                if children.iter().all(Option::is_none) {
+                    Some(None)
+                } else {
+                    Some(Some(Arc::new(Node::Branch(children))))
+                }
+            }
+        }
+    }
+}
+
+/// Iterator returned by [`DualSet::iter`]
+#[must_use]
+pub struct Iter<'a, T: Dual> {
+    stack: Vec<&'a Node<T>>,
+    leaf: std::slice::Iter<'a, (T::Key, T)>,
+}
+
+impl<'a, T: Dual> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((_, value)) = self.leaf.next() {
+                return Some(value);
+            }
+            match self.stack.pop()? {
+                Node::Leaf { entries, .. } => self.leaf = entries.iter(),
+                Node::Branch(children) => {
+                    self.stack.extend(children.iter().flatten().map(AsRef::as_ref));
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T: Dual> IntoIterator for &'a DualSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for DualSet<T>
+where
+    T: Dual + Clone,
+    T::Key: Hash,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = DualSet::new();
+        for value in iter {
+            set = set.insert(value);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct Test {
+        key: String,
+        value: u32,
+    }
+
+    impl Dual for Test {
+        type Key = String;
+        fn key(&self) -> &Self::Key {
+            &self.key
+        }
+    }
+
+    fn test(i: u32) -> Test {
+        Test {
+            key: i.to_string(),
+            value: i,
+        }
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let mut set = DualSet::new();
+        for i in 0..100 {
+            set = set.insert(test(i));
+        }
+        assert_eq!(set.len(), 100);
+        for i in 0..100 {
+            assert_eq!(set.get(&i.to_string()).unwrap().value, i);
+        }
+        assert!(set.get("nope").is_none());
+    }
+
+    #[test]
+    fn structural_sharing() {
+        let a: DualSet<Test> = (0..100).map(test).collect();
+        let b = a.insert(Test {
+            key: "50".into(),
+            value: 999,
+        });
+        // The edit does not disturb the original snapshot.
+        assert_eq!(a.get("50").unwrap().value, 50);
+        assert_eq!(b.get("50").unwrap().value, 999);
+        assert_eq!(a.len(), 100);
+        assert_eq!(b.len(), 100);
+    }
+
+    #[test]
+    fn remove() {
+        let a: DualSet<Test> = (0..100).map(test).collect();
+        let b = a.remove("42");
+        assert_eq!(a.len(), 100);
+        assert_eq!(b.len(), 99);
+        assert!(a.contains("42"));
+        assert!(!b.contains("42"));
+        // Removing an absent key is a no-op.
+        assert_eq!(b.remove("nope").len(), 99);
+    }
+
+    #[test]
+    fn modify() {
+        let a: DualSet<Test> = (0..10).map(test).collect();
+        let b = a.modify("3", |test| test.value += 1);
+        assert_eq!(a.get("3").unwrap().value, 3);
+        assert_eq!(b.get("3").unwrap().value, 4);
+        // Changing the key relocates the value.
+        let c = a.modify("4", |test| test.key = "four".into());
+        assert!(!c.contains("4"));
+        assert_eq!(c.get("four").unwrap().value, 4);
+        assert_eq!(c.len(), 10);
+    }
+
+    #[test]
+    fn iter() {
+        let set: DualSet<Test> = (0..50).map(test).collect();
+        let mut values: Vec<u32> = set.iter().map(|test| test.value).collect();
+        values.sort_unstable();
+        assert_eq!(values, (0..50).collect::<Vec<_>>());
+    }
+}